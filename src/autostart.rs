@@ -0,0 +1,117 @@
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result};
+use tracing::info;
+use winreg::RegKey;
+use winreg::enums::{HKEY_CURRENT_USER, KEY_QUERY_VALUE, KEY_SET_VALUE};
+
+use crate::config::Config;
+
+const RUN_KEY_PATH: &str = r"Software\Microsoft\Windows\CurrentVersion\Run";
+const RUN_VALUE_NAME: &str = "Heim";
+/// Holds the PID of the process `install_user_autostart` last spawned, so
+/// `uninstall_user_autostart` can kill that specific process instead of
+/// every `heim.exe` on the system (which would include its own caller).
+const RUN_PID_VALUE_NAME: &str = "HeimPid";
+
+fn current_exe_path() -> Result<PathBuf> {
+    Ok(std::env::current_exe()?)
+}
+
+/// Builds the argv for the hidden `run` subcommand, which is what actually
+/// starts Heim in the foreground - there's no SCM here to start it for us.
+fn launch_args(config: &Config) -> Vec<String> {
+    vec![
+        "run".to_string(),
+        "--log".to_string(),
+        config.log.path.clone(),
+        "--port".to_string(),
+        config.host.port.to_string(),
+    ]
+}
+
+/// Wraps `arg` in double quotes if it contains whitespace, escaping any
+/// embedded quotes, so the HKCU Run value splits back into the same argv
+/// at next logon regardless of spaces in e.g. the log path.
+fn quote_arg(arg: &str) -> String {
+    if arg.chars().any(char::is_whitespace) {
+        format!("\"{}\"", arg.replace('"', "\\\""))
+    } else {
+        arg.to_string()
+    }
+}
+
+/// Registers the current exe (with its launch args) under the HKCU Run
+/// key so it starts at logon without needing administrator rights, then
+/// spawns it immediately since there is no SCM to start it for us.
+pub fn install_user_autostart(config: &Config) -> Result<()> {
+    let exe = current_exe_path()?;
+    let args = launch_args(config);
+
+    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+    let (run_key, _) = hkcu
+        .create_subkey(RUN_KEY_PATH)
+        .context("failed to open HKCU Run key")?;
+    let command = std::iter::once(quote_arg(&exe.display().to_string()))
+        .chain(args.iter().map(|arg| quote_arg(arg)))
+        .collect::<Vec<_>>()
+        .join(" ");
+    run_key
+        .set_value(RUN_VALUE_NAME, &command)
+        .context("failed to write HKCU Run value")?;
+
+    let child = Command::new(&exe)
+        .args(&args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("failed to spawn Heim after registering autostart")?;
+    run_key
+        .set_value(RUN_PID_VALUE_NAME, &child.id())
+        .context("failed to record spawned Heim PID")?;
+
+    info!("Registered HKCU autostart and launched Heim.");
+    Ok(())
+}
+
+/// Removes the HKCU Run autostart entry and terminates the running
+/// instance, since uninstalling here doesn't go through the SCM.
+pub fn uninstall_user_autostart() -> Result<()> {
+    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+    let pid = hkcu
+        .open_subkey_with_flags(RUN_KEY_PATH, KEY_QUERY_VALUE)
+        .and_then(|run_key| run_key.get_value::<u32, _>(RUN_PID_VALUE_NAME))
+        .ok();
+
+    if let Ok(run_key) = hkcu.open_subkey_with_flags(RUN_KEY_PATH, KEY_SET_VALUE) {
+        let _ = run_key.delete_value(RUN_VALUE_NAME);
+        let _ = run_key.delete_value(RUN_PID_VALUE_NAME);
+    }
+
+    terminate_running_instance(pid)?;
+    info!("Removed HKCU autostart and stopped Heim.");
+    Ok(())
+}
+
+/// Kills the specific process `install_user_autostart` last spawned, by
+/// PID, rather than every process sharing its image name - `/IM` would
+/// also match the `heim.exe` invoking this very function.
+fn terminate_running_instance(pid: Option<u32>) -> Result<()> {
+    let Some(pid) = pid else {
+        info!("No running Heim process found to stop.");
+        return Ok(());
+    };
+
+    let status = Command::new("taskkill")
+        .args(["/F", "/PID", &pid.to_string()])
+        .status()
+        .context("failed to invoke taskkill")?;
+
+    if !status.success() {
+        info!("No running Heim process found to stop.");
+    }
+
+    Ok(())
+}
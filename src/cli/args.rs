@@ -1,6 +1,6 @@
 use clap::{Parser, Subcommand};
 
-#[cfg(not(feature="win-service"))]
+#[cfg(feature = "win-service")]
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 pub struct Cli {
@@ -9,7 +9,7 @@ pub struct Cli {
 
 }
 
-#[cfg(not(feature="win-service"))]
+#[cfg(feature = "win-service")]
 #[derive(Debug, Subcommand)]
 pub enum Commands {
     /// Install the service (admin required)
@@ -19,5 +19,19 @@ pub enum Commands {
     /// Start the service
     Start,
     /// Stop the service
-    Stop
+    Stop,
+    /// Register Heim to start at logon via the HKCU Run key (no admin required)
+    InstallUser,
+    /// Remove the HKCU Run autostart entry and stop the running process
+    UninstallUser,
+    /// Run Heim directly in the foreground, without the SCM. Used
+    /// internally by `InstallUser`/the HKCU Run key; not meant to be
+    /// typed by hand.
+    #[command(hide = true)]
+    Run {
+        #[arg(long)]
+        log: String,
+        #[arg(long)]
+        port: u16,
+    },
 }
\ No newline at end of file
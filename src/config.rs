@@ -1,9 +1,40 @@
 use serde::Deserialize;
 use std::{fs, path::Path};
 
+use crate::error::HeimError;
+
 #[derive(Debug, Deserialize)]
 pub struct Log {
     pub path: String,
+    #[serde(default)]
+    pub rotation: LogRotation,
+    #[serde(default = "default_log_level")]
+    pub level: String,
+}
+
+impl Log {
+    /// Builds a `Log` config from just a path, for call sites (like the
+    /// Windows service entry point) that only have the path on hand.
+    pub fn from_path(path: String) -> Self {
+        Self {
+            path,
+            rotation: LogRotation::default(),
+            level: default_log_level(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogRotation {
+    Hourly,
+    #[default]
+    Daily,
+    Never,
+}
+
+fn default_log_level() -> String {
+    "info".to_string()
 }
 
 #[derive(Debug, Deserialize)]
@@ -23,8 +54,15 @@ pub struct Config {
     pub host: Host,
 }
 
-pub fn load_config() -> Result<Config, anyhow::Error> {
-    let text = fs::read_to_string("Config.toml")?;
-    let cfg: Config = toml::from_str(&text)?;
+pub fn load_config() -> Result<Config, HeimError> {
+    let path = Path::new("Config.toml");
+    let text = fs::read_to_string(path).map_err(|source| HeimError::ReadFile {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let cfg: Config = toml::from_str(&text).map_err(|source| HeimError::ParseToml {
+        path: path.to_path_buf(),
+        source,
+    })?;
     Ok(cfg)
 }
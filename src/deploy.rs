@@ -0,0 +1,486 @@
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    io::Cursor,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Context;
+use axum::{
+    Json,
+    extract::{Multipart, State},
+    http::StatusCode,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures_util::{Stream, future::join_all};
+use serde::Serialize;
+use tokio::sync::broadcast;
+use tokio_stream::{StreamExt as _, wrappers::BroadcastStream};
+use tracing::{info, warn};
+
+use crate::error::HeimError;
+use crate::heim::{Heim, Run};
+use crate::probe::Status;
+
+/// How many in-flight `DeployEvent`s the broadcast channel buffers before
+/// a slow SSE subscriber starts missing them.
+const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/// Shared state for the HTTP server: the currently loaded `Heim.json`
+/// (kept in sync with disk by the file watcher in the console build's
+/// `main`; the service and `run` entry points load it once at startup),
+/// and the broadcast channel `/deploy` publishes progress to for
+/// `/deploy/events`.
+#[derive(Clone)]
+pub struct AppState {
+    pub heim: Arc<Mutex<Heim>>,
+    pub events: broadcast::Sender<DeployEvent>,
+}
+
+impl AppState {
+    pub fn new(heim: Arc<Mutex<Heim>>) -> Self {
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Self { heim, events }
+    }
+}
+
+/// A single step of a `/deploy` run, published to subscribers of
+/// `/deploy/events` as it happens.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "step", rename_all = "snake_case")]
+pub enum DeployEvent {
+    HookStarted { artifact_id: String, hook: HookKind },
+    HookFinished { artifact_id: String, hook: HookKind },
+    BackupCreated { artifact_id: String, path: String },
+    FilesExtracted { artifact_id: String, count: usize },
+    Error { artifact_id: String, message: String },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookKind {
+    Before,
+    After,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeploySummary {
+    pub artifact_id: String,
+    pub files_extracted: usize,
+    pub backed_up_to: Option<String>,
+}
+
+/// `POST /deploy` - accepts a multipart body with an `artifact_id` text
+/// field and a `file` (zip archive) field, and deploys the archive into
+/// the matching `Artifact`'s destination under `Heim.json`'s `root_path`.
+pub async fn deploy(
+    State(state): State<AppState>,
+    mut multipart: Multipart,
+) -> Result<(StatusCode, Json<DeploySummary>), HeimError> {
+    let mut artifact_id: Option<String> = None;
+    let mut file_bytes: Option<Vec<u8>> = None;
+
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| HeimError::InvalidMultipart(e.to_string()))?
+    {
+        match field.name() {
+            Some("artifact_id") => {
+                artifact_id = Some(
+                    field
+                        .text()
+                        .await
+                        .map_err(|e| HeimError::InvalidMultipart(e.to_string()))?,
+                );
+            }
+            Some("file") => {
+                let bytes = field
+                    .bytes()
+                    .await
+                    .map_err(|e| HeimError::InvalidMultipart(e.to_string()))?;
+                file_bytes = Some(bytes.to_vec());
+            }
+            other => warn!("Ignoring unknown multipart field: {:?}", other),
+        }
+    }
+
+    let artifact_id = artifact_id.ok_or(HeimError::MissingField("artifact_id"))?;
+    let file_bytes = file_bytes.ok_or(HeimError::MissingField("file"))?;
+
+    let (root_path, artifact) = {
+        let heim = state.heim.lock().unwrap();
+        let artifact = heim
+            .deploy
+            .artifacts
+            .iter()
+            .find(|a| a.id == artifact_id)
+            .cloned()
+            .ok_or_else(|| HeimError::ArtifactNotFound {
+                artifact_id: artifact_id.clone(),
+            })?;
+        (heim.deploy.root_path.clone(), artifact)
+    };
+
+    let destination = Path::new(&root_path).join(&artifact.destination);
+    let publish = |event: DeployEvent| {
+        let _ = state.events.send(event);
+    };
+
+    if let Some(run_before) = &artifact.run_before {
+        publish(DeployEvent::HookStarted {
+            artifact_id: artifact_id.clone(),
+            hook: HookKind::Before,
+        });
+        run_hook(run_before).await.map_err(|source| {
+            let error = HeimError::Hook {
+                artifact_id: artifact_id.clone(),
+                source,
+            };
+            publish(DeployEvent::Error {
+                artifact_id: artifact_id.clone(),
+                message: error.chain(),
+            });
+            error
+        })?;
+        publish(DeployEvent::HookFinished {
+            artifact_id: artifact_id.clone(),
+            hook: HookKind::Before,
+        });
+    }
+
+    let backed_up_to = if artifact.backup && destination.exists() {
+        let path = backup_destination(&destination).map_err(|source| {
+            let error = HeimError::Io {
+                artifact_id: artifact_id.clone(),
+                source,
+            };
+            publish(DeployEvent::Error {
+                artifact_id: artifact_id.clone(),
+                message: error.chain(),
+            });
+            error
+        })?;
+        publish(DeployEvent::BackupCreated {
+            artifact_id: artifact_id.clone(),
+            path: path.clone(),
+        });
+        Some(path)
+    } else {
+        None
+    };
+
+    let excluded = artifact.excluded_files.clone().unwrap_or_default();
+    let files_extracted = extract_archive(&file_bytes, &destination, &excluded).map_err(|source| {
+        let error = HeimError::Extraction {
+            artifact_id: artifact_id.clone(),
+            source,
+        };
+        publish(DeployEvent::Error {
+            artifact_id: artifact_id.clone(),
+            message: error.chain(),
+        });
+        error
+    })?;
+    publish(DeployEvent::FilesExtracted {
+        artifact_id: artifact_id.clone(),
+        count: files_extracted,
+    });
+
+    if let Some(run_after) = &artifact.run_after {
+        publish(DeployEvent::HookStarted {
+            artifact_id: artifact_id.clone(),
+            hook: HookKind::After,
+        });
+        run_hook(run_after).await.map_err(|source| {
+            let error = HeimError::Hook {
+                artifact_id: artifact_id.clone(),
+                source,
+            };
+            publish(DeployEvent::Error {
+                artifact_id: artifact_id.clone(),
+                message: error.chain(),
+            });
+            error
+        })?;
+        publish(DeployEvent::HookFinished {
+            artifact_id: artifact_id.clone(),
+            hook: HookKind::After,
+        });
+    }
+
+    info!(
+        "Deployed artifact '{}' to {}",
+        artifact_id,
+        destination.display()
+    );
+
+    Ok((
+        StatusCode::OK,
+        Json(DeploySummary {
+            artifact_id,
+            files_extracted,
+            backed_up_to,
+        }),
+    ))
+}
+
+/// `GET /deploy/events` - streams `DeployEvent`s published by `/deploy` as
+/// Server-Sent Events, so multiple clients can watch a deployment live.
+pub async fn deploy_events(
+    State(state): State<AppState>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let stream = BroadcastStream::new(state.events.subscribe()).filter_map(|msg| match msg {
+        Ok(event) => Some(Ok(Event::default()
+            .json_data(event)
+            .unwrap_or_else(|_| Event::default().data("serialization error")))),
+        Err(_) => None,
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::new().interval(Duration::from_secs(15)))
+}
+
+/// Aggregate rollup of all artifacts' probe results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rollup {
+    Ok,
+    Degraded,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HealthReport {
+    pub rollup: Rollup,
+    pub artifacts: HashMap<String, Status>,
+}
+
+/// `GET /health` - runs every artifact's configured health probe
+/// concurrently and reports the result, plus an aggregate rollup.
+pub async fn health(State(state): State<AppState>) -> Json<HealthReport> {
+    let artifacts = {
+        let heim = state.heim.lock().unwrap();
+        heim.deploy.artifacts.clone()
+    };
+
+    let checks = artifacts.iter().filter_map(|artifact| {
+        artifact.health.as_ref().map(|probe| {
+            let id = artifact.id.clone();
+            let probe = probe.clone();
+            async move { (id, probe.check().await) }
+        })
+    });
+
+    let artifacts: HashMap<String, Status> = join_all(checks).await.into_iter().collect();
+    let rollup = if artifacts.values().all(|status| *status == Status::Ok) {
+        Rollup::Ok
+    } else {
+        Rollup::Degraded
+    };
+
+    Json(HealthReport { rollup, artifacts })
+}
+
+/// Moves an existing destination directory aside to a timestamped folder
+/// next to it, returning the path it was moved to.
+fn backup_destination(destination: &Path) -> std::io::Result<String> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let name = destination
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("artifact");
+    let backup_path = destination.with_file_name(format!("{name}.bak.{timestamp}"));
+    std::fs::rename(destination, &backup_path)?;
+    Ok(backup_path.display().to_string())
+}
+
+/// Extracts `bytes` (a zip archive) into `destination`, skipping any entry
+/// whose path matches `excluded`. Returns the number of files written.
+fn extract_archive(bytes: &[u8], destination: &Path, excluded: &[String]) -> anyhow::Result<usize> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes))?;
+    std::fs::create_dir_all(destination)?;
+
+    let mut files_extracted = 0;
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        let Some(entry_path) = entry.enclosed_name() else {
+            warn!("Skipping unsafe path in archive: {}", entry.name());
+            continue;
+        };
+
+        if excluded.iter().any(|excluded| entry_path == Path::new(excluded)) {
+            continue;
+        }
+
+        let out_path = destination.join(&entry_path);
+
+        if entry.is_dir() {
+            std::fs::create_dir_all(&out_path)?;
+            continue;
+        }
+
+        if let Some(parent) = out_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut out_file = std::fs::File::create(&out_path)?;
+        std::io::copy(&mut entry, &mut out_file)?;
+        files_extracted += 1;
+    }
+
+    Ok(files_extracted)
+}
+
+/// Runs a `Run` hook via its resolved `(program, args)` invocation,
+/// applying `cwd`/`env` and an optional timeout, and fails if the
+/// process exits with a non-zero status.
+async fn run_hook(run: &Run) -> anyhow::Result<()> {
+    let (program, args) = run.invocation()?;
+
+    let mut command = tokio::process::Command::new(&program);
+    command
+        .args(&args)
+        .envs(&run.env)
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        // Without this, a timed-out hook keeps running detached in the
+        // background instead of being killed - `timeout` only abandons
+        // the `wait`, not the child process itself.
+        .kill_on_drop(true);
+    if let Some(cwd) = &run.cwd {
+        command.current_dir(cwd);
+    }
+
+    let child = command
+        .spawn()
+        .with_context(|| format!("failed to start hook `{program}`"))?;
+
+    let output = match run.timeout_secs {
+        Some(secs) => tokio::time::timeout(Duration::from_secs(secs), child.wait_with_output())
+            .await
+            .with_context(|| format!("hook `{program}` timed out after {secs}s"))??,
+        None => child.wait_with_output().await?,
+    };
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "hook `{program}` exited with {}\nstdout: {}\nstderr: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// Builds an in-memory zip archive from `(path, contents)` entries,
+    /// for exercising `extract_archive` without touching disk.
+    fn build_zip(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options =
+            zip::write::FileOptions::<()>::default().compression_method(zip::CompressionMethod::Stored);
+        for (path, contents) in entries {
+            writer.start_file(*path, options).unwrap();
+            std::io::Write::write_all(&mut writer, contents).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "heim-deploy-test-{name}-{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_extract_archive_writes_files() {
+        let destination = temp_dir("extract-basic");
+        let zip = build_zip(&[("app.txt", b"hello"), ("nested/config.json", b"{}")]);
+
+        let count = extract_archive(&zip, &destination, &[]).unwrap();
+
+        assert_eq!(count, 2);
+        assert_eq!(
+            std::fs::read_to_string(destination.join("app.txt")).unwrap(),
+            "hello"
+        );
+        assert_eq!(
+            std::fs::read_to_string(destination.join("nested/config.json")).unwrap(),
+            "{}"
+        );
+
+        std::fs::remove_dir_all(&destination).unwrap();
+    }
+
+    #[test]
+    fn test_extract_archive_skips_excluded_files() {
+        let destination = temp_dir("extract-excluded");
+        let zip = build_zip(&[("up.ps1", b"Write-Host hi"), ("app.txt", b"hello")]);
+
+        let count = extract_archive(&zip, &destination, &["up.ps1".to_string()]).unwrap();
+
+        assert_eq!(count, 1);
+        assert!(!destination.join("up.ps1").exists());
+        assert!(destination.join("app.txt").exists());
+
+        std::fs::remove_dir_all(&destination).unwrap();
+    }
+
+    #[test]
+    fn test_extract_archive_skips_unsafe_paths() {
+        let destination = temp_dir("extract-zip-slip");
+        let zip = build_zip(&[("../../escape.txt", b"pwned"), ("app.txt", b"hello")]);
+
+        let count = extract_archive(&zip, &destination, &[]).unwrap();
+
+        assert_eq!(count, 1);
+        assert!(destination.join("app.txt").exists());
+        assert!(!destination.parent().unwrap().join("escape.txt").exists());
+
+        std::fs::remove_dir_all(&destination).unwrap();
+    }
+
+    #[test]
+    fn test_backup_destination_renames_to_timestamped_folder() {
+        let parent = temp_dir("backup-parent");
+        let destination = parent.join("artifact");
+        std::fs::create_dir_all(&destination).unwrap();
+        std::fs::write(destination.join("file.txt"), b"keep me").unwrap();
+
+        let backup_path = backup_destination(&destination).unwrap();
+
+        assert!(!destination.exists());
+        let backup_path = PathBuf::from(backup_path);
+        assert!(backup_path.exists());
+        assert!(
+            backup_path
+                .file_name()
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .starts_with("artifact.bak.")
+        );
+        assert_eq!(
+            std::fs::read_to_string(backup_path.join("file.txt")).unwrap(),
+            "keep me"
+        );
+
+        std::fs::remove_dir_all(&parent).unwrap();
+    }
+}
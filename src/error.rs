@@ -1,18 +1,112 @@
-use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
 
-#[derive(Debug)]
-pub struct HeimError {
-    pub kind: ErrorKind,
-    pub message: &'static str,
+use axum::{
+    Json,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use thiserror::Error;
+use tracing::error;
+
+/// A layered Heim error: each variant carries the context (path, artifact
+/// id, ...) relevant to where it occurred, plus the underlying cause via
+/// `#[source]`, so `load_heim`/`/deploy` failures no longer collapse into
+/// one generic message.
+#[derive(Debug, Error)]
+pub enum HeimError {
+    #[error("failed to read '{path}'")]
+    ReadFile {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("failed to parse '{path}' as JSON")]
+    ParseJson {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+
+    #[error("failed to parse '{path}' as TOML")]
+    ParseToml {
+        path: PathBuf,
+        #[source]
+        source: toml::de::Error,
+    },
+
+    #[error("no artifact with id '{artifact_id}'")]
+    ArtifactNotFound { artifact_id: String },
+
+    #[error("I/O error deploying artifact '{artifact_id}'")]
+    Io {
+        artifact_id: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("hook failed for artifact '{artifact_id}'")]
+    Hook {
+        artifact_id: String,
+        #[source]
+        source: anyhow::Error,
+    },
+
+    #[error("failed to extract archive for artifact '{artifact_id}'")]
+    Extraction {
+        artifact_id: String,
+        #[source]
+        source: anyhow::Error,
+    },
+
+    #[error("invalid multipart body: {0}")]
+    InvalidMultipart(String),
+
+    #[error("missing required field '{0}'")]
+    MissingField(&'static str),
+}
+
+impl HeimError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            HeimError::ArtifactNotFound { .. } => StatusCode::NOT_FOUND,
+            HeimError::InvalidMultipart(_) | HeimError::MissingField(_) => {
+                StatusCode::BAD_REQUEST
+            }
+            HeimError::ReadFile { .. }
+            | HeimError::ParseJson { .. }
+            | HeimError::ParseToml { .. }
+            | HeimError::Io { .. }
+            | HeimError::Hook { .. }
+            | HeimError::Extraction { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// This error joined with its full `source()` chain, e.g.
+    /// `"hook failed for artifact 'x': caused by: exit code 1"`.
+    pub fn chain(&self) -> String {
+        let mut message = self.to_string();
+        let mut source = std::error::Error::source(self);
+        while let Some(cause) = source {
+            message.push_str(": caused by: ");
+            message.push_str(&cause.to_string());
+            source = cause.source();
+        }
+        message
+    }
 }
 
-#[derive(Debug)]
-pub enum ErrorKind {
-    ArtifactError,
+#[derive(Debug, Serialize)]
+struct ErrorBody {
+    error: String,
 }
 
-impl Display for HeimError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}: {}", self.kind, self.message)
+impl IntoResponse for HeimError {
+    fn into_response(self) -> Response {
+        let status = self.status_code();
+        let message = self.chain();
+        error!("{message}");
+        (status, Json(ErrorBody { error: message })).into_response()
     }
 }
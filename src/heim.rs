@@ -1,22 +1,25 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
 use tokio::fs::File;
-use tracing::error;
 
 use serde::Deserialize;
 
-use crate::error::{ErrorKind, HeimError};
+use crate::error::HeimError;
+use crate::probe::ProbeConfig;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct Heim {
     pub deploy: Deploy,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct Deploy {
     pub root_path: String,
     pub artifacts: Vec<Artifact>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct Artifact {
     pub id: String,
     pub kind: String,
@@ -25,28 +28,64 @@ pub struct Artifact {
     pub excluded_files: Option<Vec<String>>,
     pub run_before: Option<Run>,
     pub run_after: Option<Run>,
+    pub health: Option<ProbeConfig>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct Run {
+    /// Program to execute. Required unless the deprecated `powershell`
+    /// field is used instead.
+    pub program: Option<String>,
+    #[serde(default)]
+    pub args: Vec<String>,
+    pub cwd: Option<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Kill the hook if it hasn't exited after this many seconds.
+    pub timeout_secs: Option<u64>,
+    /// Deprecated convenience that expands to
+    /// `powershell -NoProfile -Command <script>`.
+    /// Prefer `program`/`args`, which are portable and don't rely on
+    /// shell quoting.
+    #[deprecated(note = "use `program` and `args` instead")]
     pub powershell: Option<String>,
 }
 
-pub async fn load_heim(path: &str) -> Result<Heim, HeimError> {
-    let file = tokio::fs::read_to_string(path).await.map_err(|e| {
-        error!("Failed to read Heim.json: {}", e);
-        HeimError {
-            kind: ErrorKind::ArtifactError,
-            message: "Failed to read Heim.json.",
+impl Run {
+    /// Resolves this hook to a `(program, args)` invocation, expanding
+    /// the deprecated `powershell` field if `program` wasn't set.
+    #[allow(deprecated)]
+    pub fn invocation(&self) -> anyhow::Result<(String, Vec<String>)> {
+        if let Some(program) = &self.program {
+            return Ok((program.clone(), self.args.clone()));
         }
-    })?;
 
-    let heim: Heim = serde_json::from_str(&file).map_err(|e| {
-        error!("Failed to parse Heim.json: {}", e);
-        HeimError {
-            kind: ErrorKind::ArtifactError,
-            message: "Failed to read Heim.json.",
+        if let Some(script) = &self.powershell {
+            return Ok((
+                "powershell".to_string(),
+                vec![
+                    "-NoProfile".to_string(),
+                    "-Command".to_string(),
+                    script.clone(),
+                ],
+            ));
         }
+
+        anyhow::bail!("Run hook has neither `program` nor `powershell` set.")
+    }
+}
+
+pub async fn load_heim(path: &str) -> Result<Heim, HeimError> {
+    let file = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|source| HeimError::ReadFile {
+            path: PathBuf::from(path),
+            source,
+        })?;
+
+    let heim: Heim = serde_json::from_str(&file).map_err(|source| HeimError::ParseJson {
+        path: PathBuf::from(path),
+        source,
     })?;
 
     Ok(heim)
@@ -57,6 +96,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[allow(deprecated)]
     fn test_deserialize_heim_example() {
         let data = r#"
         {
@@ -119,4 +159,36 @@ mod tests {
         assert!(client.run_before.is_none());
         assert!(client.run_after.is_none());
     }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_run_invocation_expands_deprecated_powershell() {
+        let run = Run {
+            program: None,
+            args: vec![],
+            cwd: None,
+            env: HashMap::new(),
+            timeout_secs: None,
+            powershell: Some("Write-Host hi".to_string()),
+        };
+
+        let (program, args) = run.invocation().expect("should expand powershell field");
+        assert_eq!(program, "powershell");
+        assert_eq!(args, vec!["-NoProfile", "-Command", "Write-Host hi"]);
+    }
+
+    #[test]
+    fn test_run_invocation_requires_program_or_powershell() {
+        #[allow(deprecated)]
+        let run = Run {
+            program: None,
+            args: vec![],
+            cwd: None,
+            env: HashMap::new(),
+            timeout_secs: None,
+            powershell: None,
+        };
+
+        assert!(run.invocation().is_err());
+    }
 }
@@ -1,15 +1,20 @@
+#[cfg(feature = "win-service")]
+mod autostart;
 mod cli;
 mod config;
+mod deploy;
 mod error;
 mod heim;
+mod probe;
 #[cfg(feature = "win-service")]
 mod win_service;
 
+use deploy::{AppState, deploy, deploy_events, health};
 use heim::load_heim;
 
 use std::{
     net::SocketAddr,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::Mutex,
     sync::{
         Arc,
@@ -20,19 +25,19 @@ use std::{
 
 use anyhow::Result;
 use axum::{
-    Json, Router,
-    extract::{DefaultBodyLimit, Multipart, State},
-    http::StatusCode,
+    Router,
+    extract::DefaultBodyLimit,
     routing::{get, post},
 };
 use notify::{RecommendedWatcher, Watcher};
-use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc;
 use tokio::{net::TcpListener, time::sleep};
 use tracing::{error, info};
+use tracing_appender::{non_blocking::WorkerGuard, rolling::Rotation};
 use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
 
-use crate::config::{Config, load_config};
+use crate::config::{Config, Log, LogRotation, load_config};
+use crate::heim::Heim;
 
 #[cfg(feature = "win-service")]
 use crate::cli::args::{Cli, Commands};
@@ -44,56 +49,68 @@ use crate::win_service::{
 #[cfg(feature = "win-service")]
 use clap::Parser;
 
-fn init_tracing(default_level: &str) {
-    // Initialize tracing once. Safe to call multiple times; subsequent calls are no-ops.
+/// Installs a rolling file log at `log.path` (needed since a Windows
+/// service's stdout is invisible), plus a stdout layer when `console` is
+/// true. The returned guard must be kept alive for the program's
+/// duration, or the non-blocking writer stops flushing.
+fn init_tracing(log: &Log, console: bool) -> WorkerGuard {
+    let rotation = match log.rotation {
+        LogRotation::Hourly => Rotation::HOURLY,
+        LogRotation::Daily => Rotation::DAILY,
+        LogRotation::Never => Rotation::NEVER,
+    };
+
+    let log_path = Path::new(&log.path);
+    let directory = log_path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    let file_name = log_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("heim.log");
+
+    let file_appender = tracing_appender::rolling::RollingFileAppender::new(
+        rotation, directory, file_name,
+    );
+    let (non_blocking, guard) = tracing_appender::non_blocking(file_appender);
+
     let env_filter = EnvFilter::try_from_default_env()
-        .or_else(|_| EnvFilter::try_new(default_level))
+        .or_else(|_| EnvFilter::try_new(&log.level))
         .unwrap_or_else(|_| EnvFilter::new("info"));
 
+    let file_layer = fmt::layer()
+        .with_target(false)
+        .with_ansi(false)
+        .with_writer(non_blocking);
+    let console_layer = console.then(|| fmt::layer().with_target(false));
+
+    // Initialize tracing once. Safe to call multiple times; subsequent calls are no-ops.
     let _ = tracing_subscriber::registry()
         .with(env_filter)
-        .with(fmt::layer().with_target(false))
+        .with(file_layer)
+        .with(console_layer)
         .try_init();
-}
 
-#[derive(Clone)]
-struct AppState {
-    started_at: std::time::Instant,
+    guard
 }
 
 async fn root() -> &'static str {
-    "Axum + Tokio running as a Windows Service. Try GET /health or POST /echo"
+    "Axum + Tokio running as a Windows Service. Try POST /deploy"
 }
 
-// #[derive(Serialize)]
-// struct Health {
-//     status: &'static str,
-//     uptime_secs: u64,
-// }
-
-// async fn health(State(state): State<AppState>) -> Json<Health> {
-//     Json(Health {
-//         status: "ok",
-//         uptime_secs: state.started_at.elapsed().as_secs(),
-//     })
-// }
-
-// async fn copy(State(state): State<AppState>) -> Json<Health> {
-//     Json(Health {
-//         status: "ok",
-//         uptime_secs: state.started_at.elapsed().as_secs(),
-//     })
-// }
-
-async fn run_http_server(addr: SocketAddr, stop_flag: Arc<AtomicBool>) -> Result<()> {
+async fn run_http_server(
+    addr: SocketAddr,
+    stop_flag: Arc<AtomicBool>,
+    heim: Arc<Mutex<Heim>>,
+) -> Result<()> {
     let app = Router::new()
-        // .route("/health", get(health))
-        // .route("/copy", get(copy)).layer(DefaultBodyLimit::disable())
-        .route("/deploy", get(deploy))
+        .route("/", get(root))
+        .route("/deploy", post(deploy))
+        .route("/deploy/events", get(deploy_events))
+        .route("/health", get(health))
         .layer(DefaultBodyLimit::disable())
-        .with_state(AppState {
-            started_at: std::time::Instant::now(),
-        });
+        .with_state(AppState::new(heim));
     let listener = TcpListener::bind(addr).await?;
     info!("HTTP server listening on http://{addr}");
 
@@ -118,12 +135,13 @@ async fn run_http_server(addr: SocketAddr, stop_flag: Arc<AtomicBool>) -> Result
 #[cfg(not(feature = "win-service"))]
 #[tokio::main]
 async fn main() -> Result<()> {
+    let config = load_config()?;
+    let _log_guard = init_tracing(&config.log, true);
+
     let path_to_heim_file = "Heim.json";
     let path: PathBuf = path_to_heim_file.into();
-    let config = load_config().unwrap();
-    let heim = Arc::new(Mutex::new(load_heim(&path_to_heim_file).await.unwrap()));
+    let heim = Arc::new(Mutex::new(load_heim(path_to_heim_file).await?));
 
-    
     let (tx, mut rx) = mpsc::channel(1);
 
     // watch on Heim.json file
@@ -145,13 +163,13 @@ async fn main() -> Result<()> {
         loop {
             if let Some(event) = rx.recv().await {
                 if event.kind.is_modify() {
-                    match load_heim(&path_to_heim_file).await {
+                    match load_heim(path_to_heim_file).await {
                         Ok(heim) => {
                             *heim_clone.lock().unwrap() = heim;
                             info!("Heim file has been updated!")
-                        },
+                        }
                         Err(e) => {
-                            error!("{}", e)
+                            error!("{}", e.chain())
                         }
                     }
                 }
@@ -161,6 +179,9 @@ async fn main() -> Result<()> {
         }
     });
 
+    let addr: SocketAddr = format!("127.0.0.1:{}", config.host.port).parse()?;
+    run_http_server(addr, Arc::new(AtomicBool::new(false)), heim).await?;
+
     Ok(())
 }
 
@@ -186,64 +207,29 @@ fn main() -> Result<()> {
             stop_service()?;
             println!("Service stopped: {}", SERVICE_NAME);
         }
+        Commands::InstallUser => {
+            autostart::install_user_autostart(&config)?;
+            println!("Registered user autostart and launched Heim.");
+        }
+        Commands::UninstallUser => {
+            autostart::uninstall_user_autostart()?;
+            println!("Removed user autostart and stopped Heim.");
+        }
+        Commands::Run { log, port } => run_foreground(log, port)?,
     }
 
     Ok(())
 }
 
-// pub struct Artifact {
-//     pub id: String,
-//     pub file: File,
-// }
-
-async fn deploy(mut multipart: Multipart) {
-    // let file;
-    // let id;
-
-    todo!();
-    // while let Some(mut field) = multipart.next_field().await.unwrap() {
-    //     let name = field.name().unwrap().to_string();
-    //
-    //     if name == "file" {
-    //         file = field
-    //             .bytes()
-    //             .await
-    //             .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    //     } else if name == "artifact_id" {
-    //         id = field.text().await;
-    //     } else {
-    //         // Handle unknown fields
-    //         warn!("Unknown field: {}", name);
-    //         Err(StatusCode::BAD_REQUEST)
-    //     }
-    // }
-
-    // file
-
-    // Ok(Artifact { id, file })
-}
+/// Runs Heim directly in the foreground, without the SCM. This is what
+/// the HKCU Run key (and `InstallUser`'s immediate launch) actually
+/// invokes, since there's no service manager to start the process for us.
+#[cfg(feature = "win-service")]
+fn run_foreground(log_path: String, port: u16) -> Result<()> {
+    let _log_guard = init_tracing(&Log::from_path(log_path), false);
 
-// impl<S> FromRequestParts<S> for Token
-// where
-//     S: Send + Sync,
-// {
-//     type Rejection = AuthError;
-
-//     async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
-//         // Extract the token from the authorization header
-//         let TypedHeader(Authorization(bearer)) = parts
-//             .extract::<TypedHeader<Authorization<Bearer>>>()
-//             .await
-//             .map_err(|_| AuthError::InvalidToken)?;
-//         // Decode the user data
-//         let token_data = decode::<Claims>(bearer.token(), &KEYS.decoding, &Validation::default())
-//             .map_err(|_| AuthError::InvalidToken)?;
-
-//         Ok(token_data.claims)
-//     }
-// }
-
-// pub struct Token {
-//     token: String,
-//     kind: String
-// }
+    let rt = tokio::runtime::Runtime::new()?;
+    let heim = Arc::new(Mutex::new(rt.block_on(load_heim("Heim.json"))?));
+    let addr: SocketAddr = format!("127.0.0.1:{port}").parse()?;
+    rt.block_on(run_http_server(addr, Arc::new(AtomicBool::new(false)), heim))
+}
@@ -0,0 +1,97 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+/// How long a `TcpProbe`/`CommandProbe` check is allowed to run before
+/// it's treated as `Status::Down`, so one unresponsive artifact can't hang
+/// `/health` forever - it `join_all`s every artifact's probe concurrently.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Result of a single probe check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    Ok,
+    Down,
+}
+
+/// A health check that can be run against a deployed artifact.
+#[async_trait]
+pub trait Probe: Send + Sync {
+    async fn check(&self) -> Status;
+}
+
+/// GETs `url` and considers the artifact healthy on any 2xx response.
+#[derive(Debug, Clone, Deserialize)]
+pub struct HttpProbe {
+    pub url: String,
+}
+
+#[async_trait]
+impl Probe for HttpProbe {
+    async fn check(&self) -> Status {
+        match tokio::time::timeout(PROBE_TIMEOUT, reqwest::get(&self.url)).await {
+            Ok(Ok(response)) if response.status().is_success() => Status::Ok,
+            _ => Status::Down,
+        }
+    }
+}
+
+/// Considers the artifact healthy if a TCP connection to `host:port` succeeds.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TcpProbe {
+    pub host: String,
+    pub port: u16,
+}
+
+#[async_trait]
+impl Probe for TcpProbe {
+    async fn check(&self) -> Status {
+        let connect = tokio::net::TcpStream::connect((self.host.as_str(), self.port));
+        match tokio::time::timeout(PROBE_TIMEOUT, connect).await {
+            Ok(Ok(_)) => Status::Ok,
+            _ => Status::Down,
+        }
+    }
+}
+
+/// Considers the artifact healthy if running `program args...` exits 0.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CommandProbe {
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+#[async_trait]
+impl Probe for CommandProbe {
+    async fn check(&self) -> Status {
+        let mut command = tokio::process::Command::new(&self.program);
+        command.args(&self.args).kill_on_drop(true);
+
+        match tokio::time::timeout(PROBE_TIMEOUT, command.status()).await {
+            Ok(Ok(status)) if status.success() => Status::Ok,
+            _ => Status::Down,
+        }
+    }
+}
+
+/// The `health` field on an `Artifact`, tagged by `kind` in `Heim.json`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ProbeConfig {
+    Http(HttpProbe),
+    Tcp(TcpProbe),
+    Command(CommandProbe),
+}
+
+impl ProbeConfig {
+    pub async fn check(&self) -> Status {
+        match self {
+            ProbeConfig::Http(probe) => probe.check().await,
+            ProbeConfig::Tcp(probe) => probe.check().await,
+            ProbeConfig::Command(probe) => probe.check().await,
+        }
+    }
+}
@@ -1,9 +1,10 @@
-use crate::config::Config;
+use crate::config::{Config, Log};
+use crate::heim::load_heim;
 use crate::{init_tracing, run_http_server};
 use std::ffi::{OsStr, OsString};
 use std::net::SocketAddr;
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 use tokio::runtime::Runtime;
@@ -27,7 +28,7 @@ fn windows_service_main(arguments: Vec<OsString>) {
 
     let port = arguments[1].clone().into_string().unwrap();
 
-    if let Err(e) = run_service(port) {
+    if let Err(e) = run_service(log.clone(), port) {
         // Service stdout/stderr are not visible; write errors to a file.
         let _ = std::fs::write(log, format!("{e:?}"));
     }
@@ -100,8 +101,8 @@ fn service_manager(connect_flags: ServiceManagerAccess) -> anyhow::Result<Servic
     Ok(ServiceManager::local_computer(None::<&str>, connect_flags)?)
 }
 
-fn run_service(port: String) -> anyhow::Result<()> {
-    init_tracing("info");
+fn run_service(log_path: String, port: String) -> anyhow::Result<()> {
+    let _log_guard = init_tracing(&Log::from_path(log_path), false);
 
     let stop_flag = Arc::new(AtomicBool::new(false));
     let stop_for_handler = stop_flag.clone();
@@ -149,7 +150,9 @@ fn run_service(port: String) -> anyhow::Result<()> {
 
     let addr: SocketAddr = host.parse()?;
 
-    let result = rt.block_on(run_http_server(addr, stop_flag));
+    let heim = Arc::new(Mutex::new(rt.block_on(load_heim("Heim.json"))?));
+
+    let result = rt.block_on(run_http_server(addr, stop_flag, heim));
 
     if let Err(err) = &result {
         error!("Server error: {err:?}");